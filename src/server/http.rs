@@ -1,12 +1,22 @@
+use std::io::Write;
+
 use async_trait::async_trait;
-use futures::future;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hmac::{Hmac, Mac, NewMac};
 use hyper::{self, Body, Request, Response, StatusCode};
+use hyper::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, VARY, HeaderValue};
 use log::error;
 use serde::de::DeserializeOwned;
 use serde_json;
+use sha1::Sha1;
+use sha2::Sha256;
 
 use crate::util;
 
+type HmacSha256 = Hmac<Sha256>;
+type HmacSha1 = Hmac<Sha1>;
+
 #[async_trait]
 pub trait MyService {
     async fn handle(&self, req: Request<Body>) -> Response<Body>;
@@ -26,24 +36,28 @@ pub trait Handler {
     }
 }
 
+#[async_trait]
 pub trait Filter {
-    fn filter(&self, req: &Request<Body>) -> FilterResult;
+    // Takes ownership of the request since verifying some filters (e.g. a webhook signature)
+    // requires consuming the body; `FilterResult::Continue` hands the request back so it can
+    // be replayed to the wrapped handler.
+    async fn filter(&self, req: Request<Body>) -> FilterResult;
 }
 
 pub enum FilterResult {
     Halt(Response<Body>),
-    Continue,
+    Continue(Request<Body>),
 }
 
 pub struct FilteredHandler {
-    filter: Box<dyn Filter>,
-    handler: Box<dyn Handler>,
+    filter: Box<dyn Filter + Send + Sync>,
+    handler: Box<dyn Handler + Send + Sync>,
 }
 
 pub struct NotFoundHandler;
 
 impl FilteredHandler {
-    pub fn new(filter: Box<dyn Filter>, handler: Box<dyn Handler>) -> Box<FilteredHandler> {
+    pub fn new(filter: Box<dyn Filter + Send + Sync>, handler: Box<dyn Handler + Send + Sync>) -> Box<FilteredHandler> {
         Box::new(FilteredHandler {
             filter: filter,
             handler: handler,
@@ -51,19 +65,306 @@ impl FilteredHandler {
     }
 }
 
+#[async_trait]
 impl Handler for FilteredHandler {
-    fn handle(&self, req: Request<Body>) -> Response<Body> {
-        match self.filter.filter(&req) {
-            FilterResult::Halt(resp) => Box::new(future::ok(resp)),
-            FilterResult::Continue => self.handler.handle(req),
+    async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        match self.filter.filter(req).await {
+            FilterResult::Halt(resp) => resp,
+            FilterResult::Continue(req) => self.handler.handle(req).await,
         }
     }
 }
 
+#[async_trait]
 impl Handler for NotFoundHandler {
-    fn handle(&self, _: Request<Body>) -> Response<Body> {
-        Box::new(future::ok(util::new_empty_resp(StatusCode::NOT_FOUND)))
+    async fn handle(&self, _: Request<Body>) -> Response<Body> {
+        util::new_empty_resp(StatusCode::NOT_FOUND)
+    }
+}
+
+/// Adapts a `MyService` (the top-level per-connection service, e.g. `OctobotService`) into a
+/// `Handler`, so it can be composed with `Handler`-based middleware like `FilteredHandler` and
+/// `CompressedHandler` at the point where the server is assembled.
+pub struct ServiceHandler<S>(pub S);
+
+#[async_trait]
+impl<S: MyService + Send + Sync> Handler for ServiceHandler<S> {
+    async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        self.0.handle(req).await
+    }
+}
+
+/// Only runs `inner` for requests whose path matches `path` exactly; every other request
+/// continues on unfiltered. Lets a route-specific filter (e.g. `GithubSignatureFilter`) be
+/// composed around a handler that also serves other routes, without rejecting those other routes.
+pub struct PathGatedFilter {
+    path: &'static str,
+    inner: Box<dyn Filter + Send + Sync>,
+}
+
+impl PathGatedFilter {
+    pub fn new(path: &'static str, inner: Box<dyn Filter + Send + Sync>) -> PathGatedFilter {
+        PathGatedFilter { path, inner }
+    }
+}
+
+#[async_trait]
+impl Filter for PathGatedFilter {
+    async fn filter(&self, req: Request<Body>) -> FilterResult {
+        if req.uri().path() == self.path {
+            self.inner.filter(req).await
+        } else {
+            FilterResult::Continue(req)
+        }
+    }
+}
+
+/// Verifies GitHub's webhook signature against an HMAC digest of the raw request body, computed
+/// with the configured webhook secret. Prefers the `X-Hub-Signature-256` header (HMAC-SHA256);
+/// falls back to the legacy `X-Hub-Signature` (HMAC-SHA1) when that's all the sender provides.
+/// Requests with a missing, malformed, or mismatched signature are halted before they ever reach
+/// the github handler.
+pub struct GithubSignatureFilter {
+    secret: Vec<u8>,
+}
+
+impl GithubSignatureFilter {
+    pub fn new(secret: &str) -> GithubSignatureFilter {
+        GithubSignatureFilter { secret: secret.as_bytes().to_vec() }
+    }
+}
+
+enum ExpectedSignature<'a> {
+    Sha256(&'a str),
+    Sha1(&'a str),
+}
+
+#[async_trait]
+impl Filter for GithubSignatureFilter {
+    async fn filter(&self, req: Request<Body>) -> FilterResult {
+        let sha256_header = req.headers().get("X-Hub-Signature-256").cloned();
+        let sha1_header = req.headers().get("X-Hub-Signature").cloned();
+
+        let header_value = match sha256_header.as_ref().or(sha1_header.as_ref()) {
+            Some(v) => v,
+            // A secret is configured for this route, so a signature is required; there's no
+            // ambiguity about which algorithm is missing, so this is a plain bad request.
+            None => return FilterResult::Halt(util::new_msg_resp(StatusCode::BAD_REQUEST, "Missing signature header".to_string())),
+        };
+
+        let signature = match header_value.to_str() {
+            Ok(s) => s,
+            Err(_) => return FilterResult::Halt(util::new_msg_resp(StatusCode::BAD_REQUEST, "Invalid signature header".to_string())),
+        };
+
+        let expected = if sha256_header.is_some() {
+            match signature.strip_prefix("sha256=") {
+                Some(hex) => ExpectedSignature::Sha256(hex),
+                None => return FilterResult::Halt(util::new_msg_resp(StatusCode::BAD_REQUEST, "Unsupported signature algorithm".to_string())),
+            }
+        } else {
+            match signature.strip_prefix("sha1=") {
+                Some(hex) => ExpectedSignature::Sha1(hex),
+                None => return FilterResult::Halt(util::new_msg_resp(StatusCode::BAD_REQUEST, "Unsupported signature algorithm".to_string())),
+            }
+        };
+
+        let (parts, body) = req.into_parts();
+        let body_bytes = match hyper::body::to_bytes(body).await {
+            Ok(b) => b,
+            Err(e) => {
+                error!("Failed to read webhook body: {}", e);
+                return FilterResult::Halt(util::new_empty_resp(StatusCode::BAD_REQUEST));
+            }
+        };
+
+        let (computed_hex, expected_hex) = match expected {
+            ExpectedSignature::Sha256(hex) => {
+                let mut mac = match HmacSha256::new_from_slice(&self.secret) {
+                    Ok(m) => m,
+                    Err(e) => {
+                        error!("Invalid webhook secret: {}", e);
+                        return FilterResult::Halt(util::new_empty_resp(StatusCode::INTERNAL_SERVER_ERROR));
+                    }
+                };
+                mac.update(&body_bytes);
+                (to_hex(&mac.finalize().into_bytes()), hex.to_string())
+            }
+            ExpectedSignature::Sha1(hex) => {
+                let mut mac = match HmacSha1::new_from_slice(&self.secret) {
+                    Ok(m) => m,
+                    Err(e) => {
+                        error!("Invalid webhook secret: {}", e);
+                        return FilterResult::Halt(util::new_empty_resp(StatusCode::INTERNAL_SERVER_ERROR));
+                    }
+                };
+                mac.update(&body_bytes);
+                (to_hex(&mac.finalize().into_bytes()), hex.to_string())
+            }
+        };
+
+        if !secure_compare(computed_hex.as_bytes(), expected_hex.as_bytes()) {
+            return FilterResult::Halt(util::new_msg_resp(StatusCode::UNAUTHORIZED, "Invalid webhook signature".to_string()));
+        }
+
+        FilterResult::Continue(Request::from_parts(parts, Body::from(body_bytes)))
+    }
+}
+
+/// Wraps `handler` with `GithubSignatureFilter`, gated to `webhook_path` via `PathGatedFilter` so
+/// only the github webhook route is held to a signature check; every other route `handler` serves
+/// reaches it unfiltered. Called from `server::main::build_main_handler` with
+/// `config.github.webhook_secret`.
+pub fn github_webhook_handler(
+    webhook_path: &'static str,
+    secret: &str,
+    handler: Box<dyn Handler + Send + Sync>,
+) -> Box<FilteredHandler> {
+    let filter = PathGatedFilter::new(webhook_path, Box::new(GithubSignatureFilter::new(secret)));
+    FilteredHandler::new(Box::new(filter), handler)
+}
+
+// Below this size the gzip framing overhead outweighs any savings, so skip compressing.
+// Used whenever the handler isn't constructed with an explicit threshold.
+const DEFAULT_MIN_COMPRESSIBLE_LEN: usize = 860;
+
+// Content-Type prefixes that are already compressed (images, video, audio, archives, web fonts).
+// Gzipping these again wastes CPU for no size benefit, so skip them regardless of length.
+const ALREADY_COMPRESSED_CONTENT_TYPES: &[&str] = &[
+    "image/", "video/", "audio/",
+    "application/zip", "application/gzip", "application/x-gzip",
+    "application/x-bzip2", "application/x-7z-compressed", "application/x-rar-compressed",
+    "font/woff",
+];
+
+/// Wraps a `Handler` and gzip-compresses its response body when the client advertises support
+/// via `Accept-Encoding`, the response doesn't already carry a `Content-Encoding` or an
+/// already-compressed `Content-Type`, and the body is at least `min_compressible_len` bytes.
+pub struct CompressedHandler {
+    handler: Box<dyn Handler + Send + Sync>,
+    min_compressible_len: usize,
+}
+
+impl CompressedHandler {
+    pub fn new(handler: Box<dyn Handler + Send + Sync>, min_compressible_len: usize) -> Box<CompressedHandler> {
+        Box::new(CompressedHandler { handler: handler, min_compressible_len: min_compressible_len })
+    }
+}
+
+/// Wraps `handler`'s responses with gzip compression (see `CompressedHandler`). Wired around the
+/// whole service in `server::main::build_main_handler`, passing `config.main.gzip_min_bytes`
+/// through as the configurable threshold.
+pub fn compressed_handler(handler: Box<dyn Handler + Send + Sync>, min_compressible_len: Option<usize>) -> Box<CompressedHandler> {
+    CompressedHandler::new(handler, min_compressible_len.unwrap_or(DEFAULT_MIN_COMPRESSIBLE_LEN))
+}
+
+// Adds "Accept-Encoding" to the response's `Vary` header without discarding whatever the wrapped
+// handler already put there (e.g. `Vary: Cookie` on a session-specific page) — appending instead
+// of overwriting keeps that cache-variance information intact for shared caches.
+fn add_vary_accept_encoding(headers: &mut hyper::HeaderMap) {
+    const TOKEN: &str = "Accept-Encoding";
+    let merged = match headers.get(VARY).and_then(|v| v.to_str().ok()) {
+        Some(existing) if existing.split(',').any(|t| t.trim().eq_ignore_ascii_case(TOKEN)) => return,
+        Some(existing) => format!("{}, {}", existing, TOKEN),
+        None => TOKEN.to_string(),
+    };
+    if let Ok(value) = HeaderValue::from_str(&merged) {
+        headers.insert(VARY, value);
+    }
+}
+
+// Per RFC 7231 §5.3.4, a directive's `q` parameter is a preference weight, and `q=0` specifically
+// means "not acceptable" — a client sending `Accept-Encoding: gzip;q=0` is asking NOT to get gzip
+// back (e.g. to compare an uncompressed checksum), not merely deprioritizing it.
+fn accept_encoding_allows_gzip(value: &str) -> bool {
+    value.split(',').any(|enc| {
+        let mut parts = enc.split(';').map(str::trim);
+        let name = match parts.next() {
+            Some(name) => name,
+            None => return false,
+        };
+        if !name.starts_with("gzip") {
+            return false;
+        }
+        let rejected = parts.any(|param| {
+            param
+                .strip_prefix("q=")
+                .and_then(|q| q.parse::<f32>().ok())
+                .map(|q| q == 0.0)
+                .unwrap_or(false)
+        });
+        !rejected
+    })
+}
+
+#[async_trait]
+impl Handler for CompressedHandler {
+    async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        let accepts_gzip = req.headers()
+            .get(ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .map(accept_encoding_allows_gzip)
+            .unwrap_or(false);
+
+        let mut resp = self.handler.handle(req).await;
+        // Set this regardless of whether the response ends up compressed, so shared caches know
+        // not to serve a gzipped response to a client that didn't ask for it (or vice versa).
+        add_vary_accept_encoding(resp.headers_mut());
+
+        let already_compressed = resp.headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| ALREADY_COMPRESSED_CONTENT_TYPES.iter().any(|prefix| v.starts_with(prefix)))
+            .unwrap_or(false);
+
+        if !accepts_gzip || already_compressed || resp.headers().contains_key(CONTENT_ENCODING) {
+            return resp;
+        }
+
+        let (mut parts, body) = resp.into_parts();
+        let body_bytes = match hyper::body::to_bytes(body).await {
+            Ok(b) => b,
+            Err(e) => {
+                error!("Failed to buffer response body for compression: {}", e);
+                return Response::from_parts(parts, Body::empty());
+            }
+        };
+
+        if body_bytes.len() < self.min_compressible_len {
+            return Response::from_parts(parts, Body::from(body_bytes));
+        }
+
+        let compressed = match gzip(&body_bytes) {
+            Ok(c) => c,
+            Err(e) => {
+                error!("Failed to gzip response body: {}", e);
+                return Response::from_parts(parts, Body::from(body_bytes));
+            }
+        };
+
+        parts.headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        parts.headers.insert(CONTENT_LENGTH, HeaderValue::from_str(&compressed.len().to_string()).unwrap());
+        Response::from_parts(parts, Body::from(compressed))
+    }
+}
+
+fn gzip(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Avoids short-circuiting on the first differing byte so comparison time doesn't leak how much
+// of the expected signature was guessed correctly.
+fn secure_compare(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
     }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
 }
 
 pub fn parse_json<T: DeserializeOwned, F>(req: Request<Body>, func: F) -> Response<Body>
@@ -81,3 +382,205 @@ where
         func(obj)
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secure_compare_matches_equal_bytes() {
+        assert!(secure_compare(b"abcdef", b"abcdef"));
+    }
+
+    #[test]
+    fn secure_compare_rejects_different_bytes_same_length() {
+        assert!(!secure_compare(b"abcdef", b"abcdeg"));
+    }
+
+    #[test]
+    fn secure_compare_rejects_different_lengths() {
+        assert!(!secure_compare(b"abc", b"abcd"));
+    }
+
+    fn sha256_signature(secret: &str, body: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha256={}", to_hex(&mac.finalize().into_bytes()))
+    }
+
+    fn sha1_signature(secret: &str, body: &[u8]) -> String {
+        let mut mac = HmacSha1::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha1={}", to_hex(&mac.finalize().into_bytes()))
+    }
+
+    async fn filter_with(header_name: &str, header_value: &str, secret: &str, body: &'static str) -> FilterResult {
+        let req = Request::builder()
+            .header(header_name, header_value)
+            .body(Body::from(body))
+            .unwrap();
+        GithubSignatureFilter::new(secret).filter(req).await
+    }
+
+    #[tokio::test]
+    async fn accepts_a_valid_sha256_signature() {
+        let body = "{\"action\":\"opened\"}";
+        let sig = sha256_signature("s3cret", body.as_bytes());
+        match filter_with("X-Hub-Signature-256", &sig, "s3cret", body).await {
+            FilterResult::Continue(_) => {}
+            FilterResult::Halt(_) => panic!("expected a valid signature to pass"),
+        }
+    }
+
+    #[tokio::test]
+    async fn accepts_a_valid_legacy_sha1_signature_when_sha256_is_absent() {
+        let body = "{\"action\":\"opened\"}";
+        let sig = sha1_signature("s3cret", body.as_bytes());
+        match filter_with("X-Hub-Signature", &sig, "s3cret", body).await {
+            FilterResult::Continue(_) => {}
+            FilterResult::Halt(_) => panic!("expected a valid legacy signature to pass"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_a_mismatched_signature() {
+        let body = "{\"action\":\"opened\"}";
+        let sig = sha256_signature("wrong-secret", body.as_bytes());
+        match filter_with("X-Hub-Signature-256", &sig, "s3cret", body).await {
+            FilterResult::Halt(resp) => assert_eq!(resp.status(), StatusCode::UNAUTHORIZED),
+            FilterResult::Continue(_) => panic!("expected a mismatched signature to be rejected"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_a_missing_signature_header() {
+        let req = Request::builder().body(Body::from("{}")).unwrap();
+        match GithubSignatureFilter::new("s3cret").filter(req).await {
+            FilterResult::Halt(resp) => assert_eq!(resp.status(), StatusCode::BAD_REQUEST),
+            FilterResult::Continue(_) => panic!("expected a missing signature to be rejected"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_a_malformed_signature_header() {
+        match filter_with("X-Hub-Signature-256", "not-a-valid-signature", "s3cret", "{}").await {
+            FilterResult::Halt(resp) => assert_eq!(resp.status(), StatusCode::BAD_REQUEST),
+            FilterResult::Continue(_) => panic!("expected a malformed signature to be rejected"),
+        }
+    }
+
+    struct FixedHandler {
+        content_type: Option<&'static str>,
+        body: String,
+    }
+
+    #[async_trait]
+    impl Handler for FixedHandler {
+        async fn handle(&self, _: Request<Body>) -> Response<Body> {
+            let mut builder = Response::builder();
+            if let Some(ct) = self.content_type {
+                builder = builder.header(CONTENT_TYPE, ct);
+            }
+            builder.body(Body::from(self.body.clone())).unwrap()
+        }
+    }
+
+    fn gzip_request(accept_encoding: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder();
+        if let Some(v) = accept_encoding {
+            builder = builder.header(ACCEPT_ENCODING, v);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn compresses_a_body_at_or_above_the_threshold() {
+        let handler = CompressedHandler::new(Box::new(FixedHandler { content_type: None, body: "x".repeat(100) }), 50);
+        let resp = handler.handle(gzip_request(Some("gzip"))).await;
+        assert_eq!(resp.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+    }
+
+    #[tokio::test]
+    async fn skips_compression_below_the_threshold() {
+        let handler = CompressedHandler::new(Box::new(FixedHandler { content_type: None, body: "short".to_string() }), 50);
+        let resp = handler.handle(gzip_request(Some("gzip"))).await;
+        assert!(resp.headers().get(CONTENT_ENCODING).is_none());
+    }
+
+    #[tokio::test]
+    async fn skips_compression_when_the_client_does_not_accept_gzip() {
+        let handler = CompressedHandler::new(Box::new(FixedHandler { content_type: None, body: "x".repeat(100) }), 50);
+        let resp = handler.handle(gzip_request(None)).await;
+        assert!(resp.headers().get(CONTENT_ENCODING).is_none());
+    }
+
+    #[tokio::test]
+    async fn skips_compression_when_the_client_explicitly_forbids_gzip_via_q0() {
+        let handler = CompressedHandler::new(Box::new(FixedHandler { content_type: None, body: "x".repeat(100) }), 50);
+        let resp = handler.handle(gzip_request(Some("gzip;q=0"))).await;
+        assert!(resp.headers().get(CONTENT_ENCODING).is_none());
+    }
+
+    #[test]
+    fn accept_encoding_allows_gzip_accepts_plain_gzip() {
+        assert!(accept_encoding_allows_gzip("gzip"));
+    }
+
+    #[test]
+    fn accept_encoding_allows_gzip_accepts_a_nonzero_q_value() {
+        assert!(accept_encoding_allows_gzip("gzip;q=0.5"));
+    }
+
+    #[test]
+    fn accept_encoding_allows_gzip_accepts_gzip_among_other_encodings() {
+        assert!(accept_encoding_allows_gzip("deflate, gzip, br"));
+    }
+
+    #[test]
+    fn accept_encoding_allows_gzip_rejects_q0() {
+        assert!(!accept_encoding_allows_gzip("gzip;q=0"));
+    }
+
+    #[test]
+    fn accept_encoding_allows_gzip_rejects_q0_with_whitespace() {
+        assert!(!accept_encoding_allows_gzip("gzip; q=0.0"));
+    }
+
+    #[test]
+    fn accept_encoding_allows_gzip_rejects_when_absent() {
+        assert!(!accept_encoding_allows_gzip("deflate, br"));
+    }
+
+    #[tokio::test]
+    async fn skips_compression_for_an_already_compressed_content_type() {
+        let handler = CompressedHandler::new(
+            Box::new(FixedHandler { content_type: Some("image/png"), body: "x".repeat(100) }),
+            50,
+        );
+        let resp = handler.handle(gzip_request(Some("gzip"))).await;
+        assert!(resp.headers().get(CONTENT_ENCODING).is_none());
+    }
+
+    #[test]
+    fn vary_header_is_set_when_absent() {
+        let mut headers = hyper::HeaderMap::new();
+        add_vary_accept_encoding(&mut headers);
+        assert_eq!(headers.get(VARY).unwrap(), "Accept-Encoding");
+    }
+
+    #[test]
+    fn vary_header_is_appended_to_an_existing_value() {
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(VARY, HeaderValue::from_static("Cookie"));
+        add_vary_accept_encoding(&mut headers);
+        assert_eq!(headers.get(VARY).unwrap(), "Cookie, Accept-Encoding");
+    }
+
+    #[test]
+    fn vary_header_is_not_duplicated_if_already_present() {
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(VARY, HeaderValue::from_static("Accept-Encoding"));
+        add_vary_accept_encoding(&mut headers);
+        assert_eq!(headers.get(VARY).unwrap(), "Accept-Encoding");
+    }
+}
@@ -1,15 +1,23 @@
 use std::fs;
-use std::io;
+use std::io::{self, Read};
 use std::net::SocketAddr;
+use std::os::unix::fs::PermissionsExt;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
+use futures::future::{join_all, FutureExt, Shared};
 use log::{error, info, warn};
 use hyper::{Request, Body};
 use hyper::server::Server;
+use hyper::server::accept::Accept;
+use hyper::server::conn::Connected;
 use hyper::service::service_fn;
 use hyper::service::make_service_fn;
 use hyper::server::conn::AddrStream;
 use tokio;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
 use tokio_tls;
 use native_tls::{self, Identity};
 
@@ -22,19 +30,462 @@ use crate::server::github_handler::GithubHandlerState;
 use crate::server::octobot_service::OctobotService;
 use crate::server::redirect_service::RedirectService;
 use crate::server::sessions::Sessions;
-use crate::server::http::MyService;
+use crate::server::http::{compressed_handler, github_webhook_handler, Handler, MyService, ServiceHandler};
+
+/// Where the main service listens, as selected by `config.main.listen_addr`.
+enum MainListenAddr {
+    Tcp(SocketAddr),
+    Unix(String),
+}
 
 pub fn start(config: Config) {
     let num_http_threads = std::cmp::max(2, config.main.num_http_threads.unwrap_or(20));
 
     let rt = runtime::new(num_http_threads, "runtime");
     rt.block_on(async move {
-        run_server(config)
+        run_server(config).await
     });
 }
 
+const PROXY_V2_SIGNATURE: [u8; 12] = [0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A];
+
+/// Mirrors hyper's legacy `AddrIncoming`: after an accept error, wait this long before retrying
+/// the accept instead of looping straight back into it. Logging-and-immediately-retrying never
+/// returns `Pending` while the failure persists (e.g. an EMFILE that won't clear until some other
+/// fd closes), so it busy-spins the runtime thread it's polled on instead of just surviving the
+/// error.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Polls an in-progress accept-error backoff, if any, clearing it once elapsed. `Poll::Ready(())`
+/// means it's safe to (re)attempt the accept; `Poll::Pending` means the caller should propagate
+/// `Pending` and wait for the next wakeup.
+fn poll_accept_backoff(backoff: &mut Option<Pin<Box<dyn std::future::Future<Output = ()> + Send>>>, cx: &mut Context) -> Poll<()> {
+    if let Some(sleep) = backoff {
+        match sleep.as_mut().poll(cx) {
+            Poll::Ready(()) => {
+                *backoff = None;
+                Poll::Ready(())
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    } else {
+        Poll::Ready(())
+    }
+}
+
+/// The github webhook endpoint. Matched exactly by `PathGatedFilter` so `GithubSignatureFilter`
+/// only applies there; every other route served by `main_service` (the web UI, other API
+/// endpoints) passes through unfiltered.
+const GITHUB_WEBHOOK_PATH: &str = "/hooks/github";
+
+/// Assembles the `Handler` chain every listener (TCP, TLS, unix) serves `main_service` through:
+/// `main_service_impl` adapted to `Handler` via `ServiceHandler`, its github webhook route held to
+/// `GithubSignatureFilter` if (and only if) `config.github.webhook_secret` is actually set, and
+/// every response passed through `CompressedHandler`, which gzips it when the client accepts gzip
+/// and the body is large enough. Shared (via the returned `Arc`) across
+/// every listener so this is wired in exactly once regardless of how many sockets are listening.
+fn build_main_handler(config: &Config, main_service_impl: OctobotService) -> Arc<dyn Handler + Send + Sync> {
+    let service_handler: Box<dyn Handler + Send + Sync> = Box::new(ServiceHandler(main_service_impl));
+
+    // An unconfigured secret means this install hasn't opted into signature verification yet;
+    // requiring it unconditionally would reject every webhook request on deployments that never
+    // set one, rather than just leaving them as unverified as they were before this landed.
+    let webhook_secret = config.github.webhook_secret.as_ref().filter(|s| !s.is_empty());
+    let routed: Box<dyn Handler + Send + Sync> = match webhook_secret {
+        Some(secret) => github_webhook_handler(GITHUB_WEBHOOK_PATH, secret, service_handler),
+        None => service_handler,
+    };
+
+    let compressed = compressed_handler(routed, config.main.gzip_min_bytes);
+    Arc::from(compressed as Box<dyn Handler + Send + Sync>)
+}
+
+/// The real client address reported by a PROXY protocol preamble, if the connection arrived via
+/// one. Handlers can recover it with `req.extensions().get::<ClientAddr>()` instead of trusting
+/// the TCP peer address, which behind a load balancer is just the balancer itself.
+#[derive(Clone, Copy)]
+pub struct ClientAddr(pub SocketAddr);
+
+/// Peels an optional PROXY protocol v1 (text) or v2 (binary) preamble off a freshly accepted TCP
+/// connection and returns the original client address it carries, falling back to `peer` (the
+/// immediate socket peer) when no preamble is present.
+async fn read_proxy_protocol(stream: &mut tokio::net::TcpStream, peer: SocketAddr) -> io::Result<SocketAddr> {
+    let mut peek_buf = [0u8; 256];
+    let n = stream.peek(&mut peek_buf).await?;
+
+    if n >= PROXY_V2_SIGNATURE.len() && peek_buf[..PROXY_V2_SIGNATURE.len()] == PROXY_V2_SIGNATURE {
+        return read_proxy_v2(stream, peer).await;
+    }
+
+    if peek_buf[..n].starts_with(b"PROXY ") {
+        return read_proxy_v1(stream, peer, &peek_buf[..n]).await;
+    }
+
+    Ok(peer)
+}
+
+async fn read_proxy_v1(
+    stream: &mut tokio::net::TcpStream,
+    peer: SocketAddr,
+    peeked: &[u8],
+) -> io::Result<SocketAddr> {
+    // The v1 header is a single CRLF-terminated line, capped at 107 bytes by the spec; if we
+    // can't find the terminator within what we peeked, give up and leave the bytes untouched.
+    let line_len = match peeked.windows(2).position(|w| w == b"\r\n") {
+        Some(pos) => pos + 2,
+        None => return Ok(peer),
+    };
+
+    let mut header = vec![0u8; line_len];
+    stream.read_exact(&mut header).await?;
+
+    let line = String::from_utf8_lossy(&header[..line_len - 2]);
+    Ok(parse_proxy_v1_line(&line).unwrap_or(peer))
+}
+
+// PROXY TCP4|TCP6 <src ip> <dst ip> <src port> <dst port>
+fn parse_proxy_v1_line(line: &str) -> Option<SocketAddr> {
+    let fields: Vec<&str> = line.trim().split(' ').collect();
+    if fields.len() >= 5 {
+        if let Ok(ip) = fields[2].parse::<std::net::IpAddr>() {
+            if let Ok(port) = fields[4].parse::<u16>() {
+                return Some(SocketAddr::new(ip, port));
+            }
+        }
+    }
+    None
+}
+
+async fn read_proxy_v2(stream: &mut tokio::net::TcpStream, peer: SocketAddr) -> io::Result<SocketAddr> {
+    let mut header = [0u8; 16];
+    stream.read_exact(&mut header).await?;
+
+    let version_command = header[12];
+    let family_protocol = header[13];
+    let addr_len = u16::from_be_bytes([header[14], header[15]]) as usize;
+
+    let mut addr_buf = vec![0u8; addr_len];
+    stream.read_exact(&mut addr_buf).await?;
+
+    Ok(parse_proxy_v2_header(version_command, family_protocol, &addr_buf).unwrap_or(peer))
+}
+
+fn parse_proxy_v2_header(version_command: u8, family_protocol: u8, addr_buf: &[u8]) -> Option<SocketAddr> {
+    // High nibble is the version (must be 2), low nibble the command: only PROXY (0x1) carries a
+    // client address; LOCAL (0x0, health checks from the proxy itself) keeps the real peer address.
+    if version_command != 0x21 {
+        return None;
+    }
+
+    match family_protocol {
+        0x11 | 0x12 if addr_buf.len() >= 12 => {
+            // AF_INET, TCP or UDP: 4-byte src addr, 4-byte dst addr, 2-byte src port, 2-byte dst port
+            let src_ip = std::net::Ipv4Addr::new(addr_buf[0], addr_buf[1], addr_buf[2], addr_buf[3]);
+            let src_port = u16::from_be_bytes([addr_buf[8], addr_buf[9]]);
+            Some(SocketAddr::new(src_ip.into(), src_port))
+        }
+        0x21 | 0x22 if addr_buf.len() >= 36 => {
+            // AF_INET6, TCP or UDP: 16-byte src addr, 16-byte dst addr, 2-byte src port, 2-byte dst port
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&addr_buf[0..16]);
+            let src_port = u16::from_be_bytes([addr_buf[32], addr_buf[33]]);
+            Some(SocketAddr::new(std::net::Ipv6Addr::from(octets).into(), src_port))
+        }
+        // An unsupported family (e.g. AF_UNSPEC or AF_UNIX) or a truncated address block; keep
+        // using the real peer address.
+        _ => None,
+    }
+}
+
+/// Wraps an accepted connection whose PROXY protocol preamble (if any) has already been
+/// consumed, so the resolved client address can ride along into `req.extensions()` via hyper's
+/// `Connected::extra`.
+struct ProxyProtocolStream<T> {
+    inner: T,
+    client_addr: SocketAddr,
+}
+
+impl<T> Connected for ProxyProtocolStream<T> {
+    fn connected(&self) -> Connected {
+        Connected::new().extra(ClientAddr(self.client_addr))
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for ProxyProtocolStream<T> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for ProxyProtocolStream<T> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+/// A `TcpListener::incoming()` replacement that, when `proxy_protocol` is enabled, strips any
+/// PROXY preamble off each connection before handing it to hyper, so `Connected::extra` carries
+/// the real client address. Parsing is opt-in (see `config.main.proxy_protocol`): a client could
+/// otherwise spoof its logged source IP by prefixing its own `PROXY` line when octobot is
+/// reachable directly rather than through a trusted load balancer.
+struct ProxyProtocolIncoming {
+    listener: Arc<tokio::net::TcpListener>,
+    proxy_protocol: bool,
+    accept_fut: Option<Pin<Box<dyn std::future::Future<Output = io::Result<ProxyProtocolStream<tokio::net::TcpStream>>> + Send>>>,
+    backoff: Option<Pin<Box<dyn std::future::Future<Output = ()> + Send>>>,
+}
+
+impl ProxyProtocolIncoming {
+    fn new(listener: tokio::net::TcpListener, proxy_protocol: bool) -> ProxyProtocolIncoming {
+        ProxyProtocolIncoming {
+            listener: Arc::new(listener),
+            proxy_protocol,
+            accept_fut: None,
+            backoff: None,
+        }
+    }
+}
+
+impl Accept for ProxyProtocolIncoming {
+    type Conn = ProxyProtocolStream<tokio::net::TcpStream>;
+    type Error = io::Error;
+
+    fn poll_accept(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<io::Result<Self::Conn>>> {
+        loop {
+            if poll_accept_backoff(&mut self.backoff, cx).is_pending() {
+                return Poll::Pending;
+            }
+
+            if self.accept_fut.is_none() {
+                let listener = self.listener.clone();
+                let proxy_protocol = self.proxy_protocol;
+                self.accept_fut = Some(Box::pin(async move {
+                    let (mut tcp, peer) = listener.accept().await?;
+                    let client_addr = if proxy_protocol {
+                        read_proxy_protocol(&mut tcp, peer).await?
+                    } else {
+                        peer
+                    };
+                    Ok(ProxyProtocolStream { inner: tcp, client_addr })
+                }));
+            }
+
+            match self.accept_fut.as_mut().unwrap().as_mut().poll(cx) {
+                Poll::Ready(Ok(conn)) => {
+                    self.accept_fut = None;
+                    return Poll::Ready(Some(Ok(conn)));
+                }
+                Poll::Ready(Err(e)) => {
+                    // A transient accept error (e.g. EMFILE/ECONNABORTED) shouldn't take the whole
+                    // listener down; log it, back off briefly (see `ACCEPT_ERROR_BACKOFF`), and
+                    // keep accepting, same as `TlsAcceptIncoming` below.
+                    error!("proxy protocol accept error: {}", e);
+                    self.accept_fut = None;
+                    self.backoff = Some(Box::pin(tokio::time::sleep(ACCEPT_ERROR_BACKOFF)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Accepts TCP connections for the HTTPS listener, optionally peels a PROXY protocol preamble
+/// (see `ProxyProtocolIncoming`), then completes the TLS handshake using whichever identity is
+/// current (see `TlsIdentity::reload`). The result is wrapped the same way as the plaintext path
+/// so the resolved client address reaches `req.extensions()` via `Connected::extra` there too,
+/// instead of only being logged and discarded.
+struct TlsAcceptIncoming {
+    listener: Arc<tokio::net::TcpListener>,
+    tls_identity: Arc<TlsIdentity>,
+    proxy_protocol: bool,
+    accept_fut: Option<Pin<Box<dyn std::future::Future<Output = io::Result<ProxyProtocolStream<tokio_tls::TlsStream<tokio::net::TcpStream>>>> + Send>>>,
+    backoff: Option<Pin<Box<dyn std::future::Future<Output = ()> + Send>>>,
+}
+
+impl TlsAcceptIncoming {
+    fn new(listener: tokio::net::TcpListener, tls_identity: Arc<TlsIdentity>, proxy_protocol: bool) -> TlsAcceptIncoming {
+        TlsAcceptIncoming {
+            listener: Arc::new(listener),
+            tls_identity,
+            proxy_protocol,
+            accept_fut: None,
+            backoff: None,
+        }
+    }
+}
+
+impl Accept for TlsAcceptIncoming {
+    type Conn = ProxyProtocolStream<tokio_tls::TlsStream<tokio::net::TcpStream>>;
+    type Error = io::Error;
+
+    fn poll_accept(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<io::Result<Self::Conn>>> {
+        loop {
+            if poll_accept_backoff(&mut self.backoff, cx).is_pending() {
+                return Poll::Pending;
+            }
+
+            if self.accept_fut.is_none() {
+                let listener = self.listener.clone();
+                let tls_identity = self.tls_identity.clone();
+                let proxy_protocol = self.proxy_protocol;
+                self.accept_fut = Some(Box::pin(async move {
+                    let (mut tcp, peer) = listener.accept().await?;
+                    // Load balancers send the PROXY preamble before the TLS ClientHello, so it
+                    // has to be peeled off here, ahead of the handshake.
+                    let client_addr = if proxy_protocol {
+                        read_proxy_protocol(&mut tcp, peer).await?
+                    } else {
+                        peer
+                    };
+                    let tls_stream = tls_identity.current().accept(tcp).await
+                        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                    Ok(ProxyProtocolStream { inner: tls_stream, client_addr })
+                }));
+            }
+
+            match self.accept_fut.as_mut().unwrap().as_mut().poll(cx) {
+                Poll::Ready(Ok(conn)) => {
+                    self.accept_fut = None;
+                    return Poll::Ready(Some(Ok(conn)));
+                }
+                Poll::Ready(Err(e)) => {
+                    // A single bad handshake (or a flaky client) shouldn't take the whole
+                    // listener down; log it, back off briefly (see `ACCEPT_ERROR_BACKOFF`) so a
+                    // sustained failure (e.g. EMFILE) doesn't busy-spin this thread, and keep
+                    // accepting.
+                    error!("tls accept error: {}", e);
+                    self.accept_fut = None;
+                    self.backoff = Some(Box::pin(tokio::time::sleep(ACCEPT_ERROR_BACKOFF)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Wraps a `tokio::net::UnixStream` so it can be handed to hyper, which needs `Connected` to be
+/// implemented for whatever `Accept::Conn` it serves. There's no peer address to report for a
+/// Unix socket, so this just uses hyper's default (empty) `Connected` info.
+struct UnixConn(tokio::net::UnixStream);
+
+impl Connected for UnixConn {
+    fn connected(&self) -> Connected {
+        Connected::new()
+    }
+}
+
+impl AsyncRead for UnixConn {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for UnixConn {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_shutdown(cx)
+    }
+}
+
+/// A `UnixListener::incoming()` replacement, mirroring `ProxyProtocolIncoming`'s shape, that
+/// drives `UnixListener::accept()` as an `Accept` stream hyper can serve directly.
+struct UnixIncoming {
+    listener: Arc<tokio::net::UnixListener>,
+    accept_fut: Option<Pin<Box<dyn std::future::Future<Output = io::Result<UnixConn>> + Send>>>,
+    backoff: Option<Pin<Box<dyn std::future::Future<Output = ()> + Send>>>,
+}
+
+impl UnixIncoming {
+    fn new(listener: tokio::net::UnixListener) -> UnixIncoming {
+        UnixIncoming {
+            listener: Arc::new(listener),
+            accept_fut: None,
+            backoff: None,
+        }
+    }
+}
+
+impl Accept for UnixIncoming {
+    type Conn = UnixConn;
+    type Error = io::Error;
+
+    fn poll_accept(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<io::Result<Self::Conn>>> {
+        loop {
+            if poll_accept_backoff(&mut self.backoff, cx).is_pending() {
+                return Poll::Pending;
+            }
+
+            if self.accept_fut.is_none() {
+                let listener = self.listener.clone();
+                self.accept_fut = Some(Box::pin(async move {
+                    let (stream, _) = listener.accept().await?;
+                    Ok(UnixConn(stream))
+                }));
+            }
+
+            match self.accept_fut.as_mut().unwrap().as_mut().poll(cx) {
+                Poll::Ready(Ok(conn)) => {
+                    self.accept_fut = None;
+                    return Poll::Ready(Some(Ok(conn)));
+                }
+                Poll::Ready(Err(e)) => {
+                    // A transient accept error shouldn't take the whole listener down; log it,
+                    // back off briefly (see `ACCEPT_ERROR_BACKOFF`) so a sustained failure doesn't
+                    // busy-spin this thread, and keep accepting, same as
+                    // `TlsAcceptIncoming`/`ProxyProtocolIncoming` above.
+                    error!("unix socket accept error: {}", e);
+                    self.accept_fut = None;
+                    self.backoff = Some(Box::pin(tokio::time::sleep(ACCEPT_ERROR_BACKOFF)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+// Resolves once a shutdown signal (SIGINT or SIGTERM) is received. Cloned (via `Shared`) into
+// every listener and hyper server so that a single signal stops new connections from being
+// accepted everywhere while in-flight requests are allowed to finish (see the
+// `with_graceful_shutdown` usages below).
+fn shutdown_signal() -> Shared<impl std::future::Future<Output = ()>> {
+    async {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to register SIGTERM handler");
+        tokio::select! {
+            res = tokio::signal::ctrl_c() => {
+                if let Err(e) = res {
+                    error!("Error listening for SIGINT: {}", e);
+                } else {
+                    info!("SIGINT received, draining connections...");
+                }
+            }
+            _ = terminate.recv() => {
+                info!("SIGTERM received, draining connections...");
+            }
+        }
+    }
+        .shared()
+}
+
 async fn run_server(config: Config) {
     let config = Arc::new(config);
+    let shutdown = shutdown_signal();
+    let mut server_tasks = Vec::new();
 
 
     let github: Arc<dyn github::api::GithubSessionFactory>;
@@ -68,9 +519,14 @@ async fn run_server(config: Config) {
         jira = None;
     }
 
-    let http_addr: SocketAddr = match config.main.listen_addr {
-        Some(ref addr_and_port) => addr_and_port.parse().unwrap(),
-        None => "0.0.0.0:3000".parse().unwrap(),
+    // `config.main.listen_addr` is either a normal `host:port` or a `unix:/path/to/socket`
+    // address requesting a Unix domain socket instead of TCP.
+    let listen_addr: MainListenAddr = match config.main.listen_addr {
+        Some(ref addr) => match addr.strip_prefix("unix:") {
+            Some(path) => MainListenAddr::Unix(path.to_string()),
+            None => MainListenAddr::Tcp(addr.parse().unwrap()),
+        },
+        None => MainListenAddr::Tcp("0.0.0.0:3000".parse().unwrap()),
     };
 
     let https_addr: SocketAddr = match config.main.listen_addr_ssl {
@@ -78,13 +534,55 @@ async fn run_server(config: Config) {
         None => "0.0.0.0:3001".parse().unwrap(),
     };
 
-    let tls_acceptor;
+    if let MainListenAddr::Unix(ref path) = listen_addr {
+        if config.main.ssl_pkcs12_file.is_some() {
+            panic!(
+                "TLS is not supported on a unix domain socket listener ({} is a unix: address); \
+                 configure config.main.listen_addr with a TCP address to use ssl_pkcs12_file",
+                path
+            );
+        }
+    }
+
+    let tls_identity: Option<Arc<TlsIdentity>>;
     if let Some(ref pkcs12_file) = config.main.ssl_pkcs12_file {
-       let identity = load_identity(pkcs12_file, &config.main.ssl_pkcs12_pass.unwrap_or(String::new()));
-       tls_acceptor = Some(tokio_tls::TlsAcceptor::from(native_tls::TlsAcceptor::builder(identity).build()?));
+        let pkcs12_pass = config.main.ssl_pkcs12_pass.clone().unwrap_or(String::new());
+        tls_identity = match TlsIdentity::new(pkcs12_file.clone(), pkcs12_pass) {
+            Ok(identity) => Some(Arc::new(identity)),
+            Err(e) => panic!("Error loading TLS identity: {}", e),
+        };
     } else {
         warn!("Warning: No SSL configured");
-        tls_acceptor = None;
+        tls_identity = None;
+    }
+
+    // Watch the pkcs12 file's mtime and reload automatically (e.g. after an ACME renewal)
+    // without dropping existing connections or restarting the process.
+    if let Some(ref tls_identity) = tls_identity {
+        let poll_interval = Duration::from_secs(config.main.ssl_reload_poll_secs.unwrap_or(30));
+        let tls_identity_poll = tls_identity.clone();
+        let poll_shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => tls_identity_poll.check_and_reload(),
+                    _ = poll_shutdown.clone() => break,
+                }
+            }
+        });
+
+        // SIGHUP stays available for operators who want to force a reload immediately rather
+        // than waiting for the next poll.
+        let tls_identity_sighup = tls_identity.clone();
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+            .expect("failed to register SIGHUP handler");
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                info!("SIGHUP received, reloading TLS identity");
+                tls_identity_sighup.reload();
+            }
+        });
     }
 
     let ui_sessions = Arc::new(Sessions::new());
@@ -93,12 +591,14 @@ async fn run_server(config: Config) {
     let main_service_impl = OctobotService::new(config.clone(), ui_sessions.clone(), github_handler_state.clone());
     let redirect_service_impl = RedirectService::new(https_addr.port());
 
+    let main_handler = build_main_handler(&config, main_service_impl);
+
     let main_service = make_service_fn(|_: &AddrStream| {
-        let service = main_service_impl.clone();
+        let handler = main_handler.clone();
         async move {
             Ok::<_, hyper::Error>(service_fn(move |req: Request<hyper::Body>| async move {
                 Ok::<_, hyper::Error>(
-                    service.handle(req)
+                    handler.handle(req)
                 )
             }))
         }
@@ -115,59 +615,279 @@ async fn run_server(config: Config) {
         }
     });
 
-    if let Some(tls_acceptor) = tls_acceptor {
+    // Opt-in, since otherwise a client talking to octobot directly could spoof its logged IP by
+    // prefixing its own PROXY line.
+    let proxy_protocol = config.main.proxy_protocol.unwrap_or(false);
+
+    if let Some(tls_identity) = tls_identity {
+        // Rejected above whenever listen_addr is a unix: address, so this is always a TCP addr.
+        let http_addr = match listen_addr {
+            MainListenAddr::Tcp(addr) => addr,
+            MainListenAddr::Unix(_) => unreachable!("TLS + unix socket listener rejected above"),
+        };
         // setup main service on https
         {
             let tcp = tokio::net::TcpListener::bind(&https_addr).await.unwrap();
-            let tls = tcp.incoming()
-                .for_each(move |tcp| {
-                    let tls_accept = tls_acceptor.accept()
-                        .then(|r| match r {
-                            Ok(x) => Ok::<_, io::Error>(Some(x)),
-                            Err(e) => {
-                                error!("tls error: {}", e);
-                                Ok::<_, io::Error>(None)
-                            }
-                        })
-                        .filter_map(|x| x);
-                    tokio::spawn(tls_accept);
-                    Ok(())
-                })
-                .map_err(|err| {
-                    error!("server error {:?}", err);
-                });
-
-            let server = Server::builder(tls).serve(main_service).map_err(|e| error!("server error: {}", e));
+            let server = Server::builder(TlsAcceptIncoming::new(tcp, tls_identity.clone(), proxy_protocol))
+                .serve(main_service)
+                .with_graceful_shutdown(shutdown.clone())
+                .map_err(|e| error!("server error: {}", e));
             info!("Listening (HTTPS) on {}", https_addr);
-            tokio::spawn(server);
+            server_tasks.push(tokio::spawn(server));
         }
         // setup http redirect
         {
-            let server = Server::bind(&http_addr).serve(redirect_service).map_err(
-                |e| error!("server error: {}", e),
-            );
+            let tcp = tokio::net::TcpListener::bind(&http_addr).await.unwrap();
+            let server = Server::builder(ProxyProtocolIncoming::new(tcp, proxy_protocol))
+                .serve(redirect_service)
+                .with_graceful_shutdown(shutdown.clone())
+                .map_err(|e| error!("server error: {}", e));
             info!("Listening (HTTP Redirect) on {}", http_addr);
-            tokio::spawn(server);
+            server_tasks.push(tokio::spawn(server));
         }
     } else {
-        // setup main service on http
-        {
-            let server = Server::bind(&http_addr).serve(main_service).map(|_| ()).map_err(
-                |e| error!("server error: {}", e),
-            );
-            info!("Listening (HTTP) on {}", http_addr);
-            tokio::spawn(server);
+        match listen_addr {
+            MainListenAddr::Tcp(ref http_addr) => {
+                let tcp = tokio::net::TcpListener::bind(http_addr).await.unwrap();
+                let server = Server::builder(ProxyProtocolIncoming::new(tcp, proxy_protocol))
+                    .serve(main_service)
+                    .with_graceful_shutdown(shutdown.clone())
+                    .map(|_| ())
+                    .map_err(|e| error!("server error: {}", e));
+                info!("Listening (HTTP) on {}", http_addr);
+                server_tasks.push(tokio::spawn(server));
+            }
+            MainListenAddr::Unix(ref unix_socket_path) => {
+                // Remove a stale socket file left behind by a previous, non-graceful exit.
+                let _ = fs::remove_file(unix_socket_path);
+                let listener = tokio::net::UnixListener::bind(unix_socket_path)
+                    .expect("failed to bind unix socket");
+                // 0660: owner and group (e.g. an nginx/haproxy running under a shared group) can
+                // connect; world access isn't needed for a local reverse-proxy socket.
+                fs::set_permissions(unix_socket_path, fs::Permissions::from_mode(0o660))
+                    .expect("failed to chmod unix socket");
+
+                let unix_handler = main_handler.clone();
+                let unix_service = make_service_fn(move |_: &UnixConn| {
+                    let handler = unix_handler.clone();
+                    async move {
+                        Ok::<_, hyper::Error>(service_fn(move |req: Request<hyper::Body>| async move {
+                            Ok::<_, hyper::Error>(
+                                handler.handle(req)
+                            )
+                        }))
+                    }
+                });
+
+                let server = Server::builder(UnixIncoming::new(listener))
+                    .serve(unix_service)
+                    .with_graceful_shutdown(shutdown.clone())
+                    .map_err(|e| error!("server error: {}", e));
+                info!("Listening (Unix) on {}", unix_socket_path);
+                server_tasks.push(tokio::spawn(server));
+            }
+        }
+    }
+
+    // Wait for the shutdown signal itself (each server is already draining towards it via
+    // `with_graceful_shutdown`), then give outstanding connections up to `shutdown_grace_secs`
+    // to finish before giving up on them so one stuck connection can't block shutdown forever.
+    shutdown.await;
+    let grace = Duration::from_secs(config.main.shutdown_grace_secs.unwrap_or(30));
+    match tokio::time::timeout(grace, join_all(server_tasks)).await {
+        Ok(results) => {
+            for result in results {
+                if let Err(e) = result {
+                    error!("server task panicked: {}", e);
+                }
+            }
+            info!("All connections drained, shutting down");
+        }
+        Err(_) => {
+            warn!("Shutdown grace period ({}s) elapsed with connections still open, exiting anyway", grace.as_secs());
         }
     }
+
+    // Remove the socket file now that the listener is done, so a graceful exit leaves no socket
+    // path behind on disk.
+    if let MainListenAddr::Unix(ref unix_socket_path) = listen_addr {
+        let _ = fs::remove_file(unix_socket_path);
+    }
 }
 
-fn load_identity(filename: &str, pass: &str) -> native_tls::Identity {
+fn load_identity(filename: &str, pass: &str) -> io::Result<Identity> {
     let mut bytes = vec![];
 
-    let file = fs::File::open(filename).expect("cannot open pkcs12 identity file");
-    file.read_to_end(&mut bytes).unwrap();
+    let mut file = fs::File::open(filename)?;
+    file.read_to_end(&mut bytes)?;
+
+    Identity::from_pkcs12(&bytes, pass).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Holds the `tokio_tls::TlsAcceptor` built from the configured pkcs12 identity behind a
+/// `RwLock`, so `reload()` can swap in a freshly-read certificate/key without tearing down the
+/// listener or any connections already in flight. `check_and_reload` compares the file's mtime
+/// against the last time we read it, so a background poller can call it cheaply and only pay
+/// for re-parsing the identity when it has actually changed on disk.
+struct TlsIdentity {
+    pkcs12_file: String,
+    pkcs12_pass: String,
+    acceptor: std::sync::RwLock<Arc<tokio_tls::TlsAcceptor>>,
+    last_modified: std::sync::Mutex<Option<std::time::SystemTime>>,
+}
+
+impl TlsIdentity {
+    fn new(pkcs12_file: String, pkcs12_pass: String) -> io::Result<TlsIdentity> {
+        let acceptor = Self::build_acceptor(&pkcs12_file, &pkcs12_pass)?;
+        let last_modified = Self::mtime(&pkcs12_file).ok();
+        Ok(TlsIdentity {
+            pkcs12_file,
+            pkcs12_pass,
+            acceptor: std::sync::RwLock::new(Arc::new(acceptor)),
+            last_modified: std::sync::Mutex::new(last_modified),
+        })
+    }
+
+    fn current(&self) -> Arc<tokio_tls::TlsAcceptor> {
+        self.acceptor.read().unwrap().clone()
+    }
+
+    fn reload(&self) {
+        match Self::build_acceptor(&self.pkcs12_file, &self.pkcs12_pass) {
+            Ok(acceptor) => {
+                *self.acceptor.write().unwrap() = Arc::new(acceptor);
+                *self.last_modified.lock().unwrap() = Self::mtime(&self.pkcs12_file).ok();
+                info!("Reloaded TLS identity from {}", self.pkcs12_file);
+            }
+            Err(e) => error!("Failed to reload TLS identity from {}: {}", self.pkcs12_file, e),
+        }
+    }
+
+    /// Reloads only if the file's mtime has moved since the last successful read. A failed stat
+    /// (e.g. the file is mid-rewrite) is logged and otherwise ignored; we just try again on the
+    /// next tick and keep serving the previous identity in the meantime.
+    fn check_and_reload(&self) {
+        let current = match Self::mtime(&self.pkcs12_file) {
+            Ok(m) => m,
+            Err(e) => {
+                error!("Failed to stat TLS identity file {}: {}", self.pkcs12_file, e);
+                return;
+            }
+        };
+
+        if *self.last_modified.lock().unwrap() != Some(current) {
+            self.reload();
+        }
+    }
+
+    fn mtime(pkcs12_file: &str) -> io::Result<std::time::SystemTime> {
+        fs::metadata(pkcs12_file)?.modified()
+    }
+
+    fn build_acceptor(pkcs12_file: &str, pkcs12_pass: &str) -> io::Result<tokio_tls::TlsAcceptor> {
+        let identity = load_identity(pkcs12_file, pkcs12_pass)?;
+        let acceptor = native_tls::TlsAcceptor::builder(identity)
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        Ok(tokio_tls::TlsAcceptor::from(acceptor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_valid_v1_tcp4_line() {
+        let addr = parse_proxy_v1_line("PROXY TCP4 192.168.0.1 192.168.0.2 56324 443").unwrap();
+        assert_eq!(addr, "192.168.0.1:56324".parse().unwrap());
+    }
+
+    #[test]
+    fn parses_a_valid_v1_tcp6_line() {
+        let addr = parse_proxy_v1_line("PROXY TCP6 ::1 ::2 56324 443").unwrap();
+        assert_eq!(addr, "[::1]:56324".parse().unwrap());
+    }
+
+    #[test]
+    fn rejects_a_v1_line_missing_fields() {
+        assert!(parse_proxy_v1_line("PROXY TCP4 192.168.0.1").is_none());
+    }
+
+    #[test]
+    fn rejects_a_v1_line_with_an_invalid_ip() {
+        assert!(parse_proxy_v1_line("PROXY TCP4 not-an-ip 192.168.0.2 56324 443").is_none());
+    }
+
+    #[test]
+    fn rejects_a_v1_line_with_an_invalid_port() {
+        assert!(parse_proxy_v1_line("PROXY TCP4 192.168.0.1 192.168.0.2 not-a-port 443").is_none());
+    }
+
+    #[test]
+    fn parses_a_valid_v2_ipv4_header() {
+        let mut addr_buf = vec![0u8; 12];
+        addr_buf[0..4].copy_from_slice(&[10, 0, 0, 1]);
+        addr_buf[8..10].copy_from_slice(&56324u16.to_be_bytes());
+        let addr = parse_proxy_v2_header(0x21, 0x11, &addr_buf).unwrap();
+        assert_eq!(addr, "10.0.0.1:56324".parse().unwrap());
+    }
+
+    #[test]
+    fn parses_a_valid_v2_ipv6_header() {
+        let mut addr_buf = vec![0u8; 36];
+        addr_buf[15] = 1;
+        addr_buf[32..34].copy_from_slice(&56324u16.to_be_bytes());
+        let addr = parse_proxy_v2_header(0x21, 0x21, &addr_buf).unwrap();
+        assert_eq!(addr, "[::1]:56324".parse().unwrap());
+    }
+
+    #[test]
+    fn rejects_a_v2_header_with_an_unsupported_command() {
+        // 0x20 is version 2 with the LOCAL command (a health check from the proxy itself), which
+        // carries no client address even with an AF_INET family and a full address block.
+        let addr_buf = vec![0u8; 12];
+        assert!(parse_proxy_v2_header(0x20, 0x11, &addr_buf).is_none());
+    }
+
+    #[test]
+    fn rejects_a_v2_header_with_a_truncated_ipv4_address() {
+        // AF_INET needs at least 12 bytes; 8 is truncated mid-header.
+        let addr_buf = vec![0u8; 8];
+        assert!(parse_proxy_v2_header(0x21, 0x11, &addr_buf).is_none());
+    }
+
+    #[test]
+    fn rejects_a_v2_header_with_a_truncated_ipv6_address() {
+        // AF_INET6 needs at least 36 bytes; 20 is truncated mid-header.
+        let addr_buf = vec![0u8; 20];
+        assert!(parse_proxy_v2_header(0x21, 0x21, &addr_buf).is_none());
+    }
+
+    #[test]
+    fn rejects_a_v2_header_with_an_unsupported_family() {
+        let addr_buf = vec![0u8; 12];
+        assert!(parse_proxy_v2_header(0x21, 0x00, &addr_buf).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn poll_accept_backoff_waits_out_an_active_sleep_before_reporting_ready() {
+        let mut backoff = Some(Box::pin(tokio::time::sleep(ACCEPT_ERROR_BACKOFF)) as Pin<Box<dyn std::future::Future<Output = ()> + Send>>);
+
+        assert!(std::future::poll_fn(|cx| Poll::Ready(poll_accept_backoff(&mut backoff, cx))).await.is_pending());
+        assert!(backoff.is_some());
+
+        tokio::time::advance(ACCEPT_ERROR_BACKOFF).await;
 
-    Identity::from_pkcs12(&bytes, pass).expect("cannot read pkcs12 identity")
+        assert!(std::future::poll_fn(|cx| Poll::Ready(poll_accept_backoff(&mut backoff, cx))).await.is_ready());
+        assert!(backoff.is_none());
+    }
+
+    #[tokio::test]
+    async fn poll_accept_backoff_is_immediately_ready_with_no_active_sleep() {
+        let mut backoff: Option<Pin<Box<dyn std::future::Future<Output = ()> + Send>>> = None;
+        assert!(std::future::poll_fn(|cx| Poll::Ready(poll_accept_backoff(&mut backoff, cx))).await.is_ready());
+    }
 }
 
 